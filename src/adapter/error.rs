@@ -0,0 +1,49 @@
+//! Error types for the `adapter` module
+
+use std::fmt::{self, Display};
+
+/// Errors communicating with a `YubiHSM 2` over one of its transports
+#[derive(Debug)]
+pub struct AdapterError {
+    kind: AdapterErrorKind,
+    description: String,
+}
+
+impl AdapterError {
+    /// Create a new `AdapterError` of the given kind with a human-readable description
+    pub fn new(kind: AdapterErrorKind, description: impl Into<String>) -> Self {
+        AdapterError {
+            kind,
+            description: description.into(),
+        }
+    }
+
+    /// Kind of error that occurred
+    pub fn kind(&self) -> AdapterErrorKind {
+        self.kind
+    }
+}
+
+impl Display for AdapterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.description)
+    }
+}
+
+impl std::error::Error for AdapterError {}
+
+/// Kinds of errors which occur talking to a `YubiHSM 2`
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AdapterErrorKind {
+    /// Invalid address (e.g. a malformed connector URL or serial number)
+    AddrInvalid,
+
+    /// No matching device could be found
+    DeviceNotFound,
+
+    /// The device could not be claimed (e.g. already in use, or a USB claim failure)
+    ConnectionFailed,
+
+    /// The underlying transport returned an I/O error
+    IoError,
+}
@@ -0,0 +1,56 @@
+//! HTTP connector: talks to a `YubiHSM 2` through a `yubihsm-connector`
+//! process
+//!
+//! <https://developers.yubico.com/yubihsm-connector/>
+
+use super::{AdapterError, AdapterErrorKind, Connection, Connector};
+
+/// Connects to a `YubiHSM 2` through a running `yubihsm-connector` process
+/// over HTTP
+#[derive(Clone, Debug)]
+pub struct HttpConnector {
+    connector_url: String,
+}
+
+impl HttpConnector {
+    /// Target the `yubihsm-connector` listening at `addr`
+    /// (e.g. `"http://127.0.0.1:12345"`)
+    pub fn new(addr: &str) -> Self {
+        HttpConnector {
+            connector_url: addr.to_owned(),
+        }
+    }
+}
+
+impl Connector for HttpConnector {
+    fn connect(&self) -> Result<Box<dyn Connection>, AdapterError> {
+        Ok(Box::new(HttpConnection {
+            connector_url: self.connector_url.clone(),
+        }))
+    }
+}
+
+/// An open HTTP connection to a `yubihsm-connector` process
+struct HttpConnection {
+    connector_url: String,
+}
+
+impl Connection for HttpConnection {
+    fn send_message(&mut self, message: Vec<u8>) -> Result<Vec<u8>, AdapterError> {
+        reqwest::Client::new()
+            .post(&format!("{}/connector/api", self.connector_url))
+            .body(message)
+            .send()
+            .and_then(|mut response| {
+                let mut body = Vec::new();
+                response.copy_to(&mut body)?;
+                Ok(body)
+            })
+            .map_err(|e| {
+                AdapterError::new(
+                    AdapterErrorKind::IoError,
+                    format!("error communicating with yubihsm-connector: {}", e),
+                )
+            })
+    }
+}
@@ -0,0 +1,36 @@
+//! Transports used to exchange messages with a `YubiHSM 2`: over HTTP via a
+//! `yubihsm-connector` process, directly over USB, or (for tests) against
+//! an in-process `MockHsm`.
+
+mod error;
+mod http;
+mod usb;
+
+pub use self::{
+    error::{AdapterError, AdapterErrorKind},
+    http::HttpConnector,
+    usb::{list_devices as list_usb_devices, UsbConnector},
+};
+
+/// Something that can establish a `Connection` to a `YubiHSM 2`.
+///
+/// `HttpConnector`, `UsbConnector`, and `mockhsm::MockHsm` are the three
+/// implementations: `Session`/command code is generic over this trait and
+/// doesn't need to know which one it was handed.
+pub trait Connector: Send + Sync {
+    /// Establish a new connection to the device
+    fn connect(&self) -> Result<Box<dyn Connection>, AdapterError>;
+}
+
+/// An open connection to a `YubiHSM 2`, capable of exchanging raw messages
+/// with it
+pub trait Connection: Send {
+    /// Send a raw message to the device and return its raw response
+    fn send_message(&mut self, message: Vec<u8>) -> Result<Vec<u8>, AdapterError>;
+}
+
+impl Connection for Box<dyn Connection> {
+    fn send_message(&mut self, message: Vec<u8>) -> Result<Vec<u8>, AdapterError> {
+        (**self).send_message(message)
+    }
+}
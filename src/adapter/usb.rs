@@ -0,0 +1,194 @@
+//! USB connector: talks directly to an attached `YubiHSM 2`, without going
+//! through a `yubihsm-connector` process.
+
+use super::{AdapterError, AdapterErrorKind, Connection, Connector};
+use crate::serial_number::SerialNumber;
+use std::time::Duration;
+
+/// Yubico's USB vendor ID
+const YUBICO_VENDOR_ID: u16 = 0x1050;
+
+/// `YubiHSM 2` USB product ID
+const YUBIHSM2_PRODUCT_ID: u16 = 0x0030;
+
+/// Timeout for USB bulk transfers
+const USB_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// USB interface owning the bulk endpoints below, which must be claimed
+/// before `libusb` will allow transfers against them
+const USB_INTERFACE_NUM: u8 = 0;
+
+/// USB bulk endpoint the `YubiHSM 2` reads commands from
+const USB_OUT_ENDPOINT: u8 = 0x01;
+
+/// USB bulk endpoint the `YubiHSM 2` writes responses to
+const USB_IN_ENDPOINT: u8 = 0x81;
+
+/// Largest message the USB connection will read in one bulk transfer
+const USB_MAX_MESSAGE_SIZE: usize = 2048;
+
+/// Connects to an attached `YubiHSM 2` over USB, optionally selecting a
+/// specific device by serial number
+#[derive(Copy, Clone, Debug)]
+pub struct UsbConnector {
+    serial: Option<SerialNumber>,
+}
+
+impl UsbConnector {
+    /// Target a `YubiHSM 2` attached over USB. When `serial` is given, only
+    /// the device with that serial number is claimed; otherwise the first
+    /// device found is used, which is sufficient for single-HSM deployments
+    /// but ambiguous with more than one device attached.
+    pub fn new(serial: Option<SerialNumber>) -> Self {
+        UsbConnector { serial }
+    }
+}
+
+impl Connector for UsbConnector {
+    fn connect(&self) -> Result<Box<dyn Connection>, AdapterError> {
+        let device = find_device(self.serial)?;
+
+        let mut handle = device.open().map_err(|e| {
+            AdapterError::new(
+                AdapterErrorKind::ConnectionFailed,
+                format!("couldn't claim YubiHSM 2 USB device: {}", e),
+            )
+        })?;
+
+        handle.claim_interface(USB_INTERFACE_NUM).map_err(|e| {
+            AdapterError::new(
+                AdapterErrorKind::ConnectionFailed,
+                format!("couldn't claim USB interface {}: {}", USB_INTERFACE_NUM, e),
+            )
+        })?;
+
+        Ok(Box::new(UsbConnection { handle }))
+    }
+}
+
+/// An open USB connection to a `YubiHSM 2`
+struct UsbConnection {
+    handle: rusb::DeviceHandle<rusb::GlobalContext>,
+}
+
+impl Connection for UsbConnection {
+    fn send_message(&mut self, message: Vec<u8>) -> Result<Vec<u8>, AdapterError> {
+        self.handle
+            .write_bulk(USB_OUT_ENDPOINT, &message, USB_TIMEOUT)
+            .map_err(|e| {
+                AdapterError::new(AdapterErrorKind::IoError, format!("USB write error: {}", e))
+            })?;
+
+        let mut response = vec![0u8; USB_MAX_MESSAGE_SIZE];
+        let nread = self
+            .handle
+            .read_bulk(USB_IN_ENDPOINT, &mut response, USB_TIMEOUT)
+            .map_err(|e| {
+                AdapterError::new(AdapterErrorKind::IoError, format!("USB read error: {}", e))
+            })?;
+
+        response.truncate(nread);
+        Ok(response)
+    }
+}
+
+/// List the serial numbers of every `YubiHSM 2` currently attached over USB.
+///
+/// A device whose serial number can't be read is left out of the list
+/// rather than aborting the whole enumeration, same as `find_device`.
+pub fn list_devices() -> Result<Vec<SerialNumber>, AdapterError> {
+    let devices = enumerate_devices()?;
+
+    Ok(devices
+        .iter()
+        .filter_map(|device| device_serial_number(device).ok())
+        .collect())
+}
+
+/// Find an attached `YubiHSM 2`, optionally restricting the search to a
+/// specific serial number
+fn find_device(serial: Option<SerialNumber>) -> Result<rusb::Device<rusb::GlobalContext>, AdapterError> {
+    let devices = enumerate_devices()?;
+
+    for device in devices {
+        match serial {
+            // A device whose serial number can't be read is skipped rather
+            // than aborting the whole search, so one misbehaving unit on
+            // the bus doesn't make every other device unreachable by
+            // serial number.
+            Some(wanted) => match device_serial_number(&device) {
+                Ok(found) if found == wanted => return Ok(device),
+                _ => continue,
+            },
+            None => return Ok(device),
+        }
+    }
+
+    Err(AdapterError::new(
+        AdapterErrorKind::DeviceNotFound,
+        match serial {
+            Some(serial) => format!("no YubiHSM 2 found with serial number {}", serial),
+            None => "no YubiHSM 2 found attached over USB".to_owned(),
+        },
+    ))
+}
+
+/// Enumerate every attached `YubiHSM 2` by USB vendor/product ID
+fn enumerate_devices() -> Result<Vec<rusb::Device<rusb::GlobalContext>>, AdapterError> {
+    let devices = rusb::devices().map_err(|e| {
+        AdapterError::new(
+            AdapterErrorKind::ConnectionFailed,
+            format!("couldn't enumerate USB devices: {}", e),
+        )
+    })?;
+
+    Ok(devices
+        .iter()
+        .filter(|device| {
+            device
+                .device_descriptor()
+                .map(|descriptor| {
+                    descriptor.vendor_id() == YUBICO_VENDOR_ID
+                        && descriptor.product_id() == YUBIHSM2_PRODUCT_ID
+                })
+                .unwrap_or(false)
+        })
+        .collect())
+}
+
+/// Read a device's serial number from its USB descriptor
+fn device_serial_number(
+    device: &rusb::Device<rusb::GlobalContext>,
+) -> Result<SerialNumber, AdapterError> {
+    let descriptor = device.device_descriptor().map_err(|e| {
+        AdapterError::new(
+            AdapterErrorKind::ConnectionFailed,
+            format!("couldn't read USB device descriptor: {}", e),
+        )
+    })?;
+
+    let handle = device.open().map_err(|e| {
+        AdapterError::new(
+            AdapterErrorKind::ConnectionFailed,
+            format!("couldn't open USB device to read its serial number: {}", e),
+        )
+    })?;
+
+    let serial_index = descriptor.serial_number_string_index().ok_or_else(|| {
+        AdapterError::new(
+            AdapterErrorKind::DeviceNotFound,
+            "USB device has no serial number string descriptor",
+        )
+    })?;
+
+    let serial_str = handle
+        .read_string_descriptor_ascii(serial_index)
+        .map_err(|e| {
+            AdapterError::new(
+                AdapterErrorKind::IoError,
+                format!("couldn't read USB serial number descriptor: {}", e),
+            )
+        })?;
+
+    serial_str.parse()
+}
@@ -0,0 +1,16 @@
+//! Commands for retrieving and acknowledging `YubiHSM 2` audit log entries
+
+mod get_log_entries;
+mod set_log_index;
+
+pub use self::{
+    get_log_entries::{
+        get_log_entries, LogDigest, LogEntries, LogEntry, LOG_DIGEST_SIZE, LOG_ENTRIES_CAPACITY,
+    },
+    set_log_index::set_log_index,
+};
+
+pub(crate) use self::{
+    get_log_entries::GetLogEntriesCommand,
+    set_log_index::{SetLogIndexCommand, SetLogIndexResponse},
+};
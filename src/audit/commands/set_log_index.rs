@@ -0,0 +1,37 @@
+//! Acknowledge audit log entries as consumed on the `YubiHSM 2` device
+//!
+//! <https://developers.yubico.com/YubiHSM2/Commands/Set_Log_Index.html>
+
+use crate::{
+    adapter::Connection,
+    command::{self, Command},
+    response::Response,
+    session::{Session, SessionError},
+};
+use serde::{Deserialize, Serialize};
+
+/// Acknowledge all log entries up to and including `item`, allowing the
+/// `YubiHSM 2` to reclaim the corresponding space in its audit log buffer
+pub fn set_log_index<C: Connection>(session: &mut Session<C>, item: u16) -> Result<(), SessionError> {
+    session.send_command(SetLogIndexCommand { item })?;
+    Ok(())
+}
+
+/// Request parameters for `command::set_log_index`
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct SetLogIndexCommand {
+    /// `item` number of the last log entry the caller has consumed
+    pub item: u16,
+}
+
+impl Command for SetLogIndexCommand {
+    type ResponseType = SetLogIndexResponse;
+}
+
+/// Response from `command::set_log_index`
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SetLogIndexResponse {}
+
+impl Response for SetLogIndexResponse {
+    const COMMAND_CODE: command::Code = command::Code::SetLogIndex;
+}
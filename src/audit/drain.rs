@@ -0,0 +1,62 @@
+//! Draining the `YubiHSM 2`'s audit log: pull buffered entries, verify the
+//! hash chain, hand them to the caller, and acknowledge them so the device
+//! can reclaim the buffer space.
+
+use crate::{
+    adapter::Connection,
+    audit::{
+        commands::{get_log_entries, set_log_index},
+        LogDigest, LogEntry, LOG_ENTRIES_CAPACITY,
+    },
+    session::{Session, SessionError, SessionErrorKind},
+};
+
+/// Repeatedly fetch audit log entries via `get_log_entries`, verify the hash
+/// chain, pass each entry to `handler` in order, then `set_log_index` up to
+/// the highest `item` processed. Keeps pulling until a fetch comes back
+/// short of `LOG_ENTRIES_CAPACITY`, i.e. the buffer wasn't full, meaning
+/// there's nothing left to gain from another round-trip. A response can
+/// never reliably come back empty: draining itself issues `get_log_entries`
+/// and `set_log_index` commands, which the device logs like any other.
+///
+/// `prev_digest` seeds the chain as in `LogEntries::verify_chain`: pass
+/// `None` to verify from the device's first boot entry, or the last digest
+/// returned by a previous call to resume from a trusted checkpoint.
+///
+/// Returns the digest of the last entry consumed, for use as `prev_digest`
+/// on a subsequent call.
+pub fn drain_log_entries<C, F>(
+    session: &mut Session<C>,
+    prev_digest: Option<LogDigest>,
+    mut handler: F,
+) -> Result<Option<LogDigest>, SessionError>
+where
+    C: Connection,
+    F: FnMut(&LogEntry),
+{
+    let mut prev_digest = prev_digest;
+
+    loop {
+        let response = get_log_entries(session)?;
+        let came_back_short = (response.num_entries as usize) < LOG_ENTRIES_CAPACITY;
+
+        if let Some(last) = response.entries.last() {
+            response
+                .verify_chain(prev_digest)
+                .map_err(|e| err!(SessionErrorKind::ProtocolError, "{}", e))?;
+
+            for entry in &response.entries {
+                handler(entry);
+            }
+
+            prev_digest = Some(last.digest);
+            set_log_index(session, last.item)?;
+        }
+
+        if came_back_short {
+            break;
+        }
+    }
+
+    Ok(prev_digest)
+}
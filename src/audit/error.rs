@@ -0,0 +1,44 @@
+//! Error types for the `audit` module
+
+use std::fmt::{self, Display};
+
+/// Errors related to audit log handling
+#[derive(Debug)]
+pub struct AuditError {
+    kind: AuditErrorKind,
+    description: String,
+}
+
+impl AuditError {
+    /// Create a new `AuditError` of the given kind with a human-readable description
+    pub fn new(kind: AuditErrorKind, description: impl Into<String>) -> Self {
+        AuditError {
+            kind,
+            description: description.into(),
+        }
+    }
+
+    /// Kind of error that occurred
+    pub fn kind(&self) -> AuditErrorKind {
+        self.kind
+    }
+}
+
+impl Display for AuditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.description)
+    }
+}
+
+impl std::error::Error for AuditError {}
+
+/// Kinds of errors which occur when verifying the audit log
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AuditErrorKind {
+    /// A log entry's digest did not match the recomputed value, indicating
+    /// the entry (or one before it) was tampered with
+    DigestMismatch,
+
+    /// The `item` sequence skipped a value, indicating a dropped log entry
+    SequenceError,
+}
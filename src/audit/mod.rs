@@ -0,0 +1,14 @@
+//! Audit logging support: reading, verifying, and acknowledging the
+//! `YubiHSM 2`'s tamper-evident internal audit log
+//!
+//! <https://developers.yubico.com/YubiHSM2/Concepts/Logs.html>
+
+pub mod commands;
+mod drain;
+mod error;
+
+pub use self::{
+    commands::{LogDigest, LogEntries, LogEntry, LOG_DIGEST_SIZE, LOG_ENTRIES_CAPACITY},
+    drain::drain_log_entries,
+    error::{AuditError, AuditErrorKind},
+};
@@ -0,0 +1,174 @@
+//! High-level client that owns a `Connector` and a `Session`, reconnecting
+//! transparently when the `YubiHSM 2` closes the session for inactivity.
+//!
+//! Raw `Connector`/`Session` usage forces callers to manually re-create a
+//! session any time the device invalidates it (see the `reset` module for
+//! one place that fragility already shows up as a bare `ProtocolError`).
+//! `Client` hides that behind a single retryable call.
+
+use crate::{
+    adapter::{Connection, Connector},
+    audit::{self, LogDigest, LogEntry},
+    session::{self, Credentials, Session, SessionError, SessionErrorKind},
+};
+use std::{
+    cell::Cell,
+    time::{Duration, Instant},
+};
+
+/// A `YubiHSM 2` client which owns its connector and credentials, lazily
+/// re-establishing its session whenever the device has closed the previous
+/// one for inactivity.
+pub struct Client<C: Connector> {
+    connector: C,
+    credentials: Credentials,
+    session: Option<Session<Box<dyn Connection>>>,
+    reconnect: bool,
+}
+
+impl<C: Connector> Client<C> {
+    /// Open a `Client` for the given connector and credentials, immediately
+    /// establishing a session so connection problems surface right away.
+    ///
+    /// When `reconnect` is set, a command that fails because the device
+    /// reports the session has expired is retried once against a freshly
+    /// established session, rather than returning the error to the caller.
+    pub fn open(connector: C, credentials: Credentials, reconnect: bool) -> Result<Self, SessionError> {
+        let mut client = Client {
+            connector,
+            credentials,
+            session: None,
+            reconnect,
+        };
+
+        client.session()?;
+        Ok(client)
+    }
+
+    /// Round-trip a ping to the device and return how long it took.
+    ///
+    /// Timed from inside the retried closure, not around `with_retry` as a
+    /// whole, so a reconnect triggered by an expired session doesn't get
+    /// counted as part of the round trip.
+    pub fn ping(&mut self) -> Result<Duration, SessionError> {
+        let mut elapsed = Duration::default();
+
+        self.with_retry(|session| {
+            let start = Instant::now();
+            let result = session.echo(b"ping".to_vec());
+            elapsed = start.elapsed();
+            result
+        })?;
+
+        Ok(elapsed)
+    }
+
+    /// Blink the device's LED for the given number of seconds, to help
+    /// physically identify it among several
+    pub fn blink(&mut self, seconds: u8) -> Result<(), SessionError> {
+        self.with_retry(|session| session.blink(seconds))
+    }
+
+    /// Drain the device's audit log: fetch entries, verify the hash chain,
+    /// pass each entry to `handler` in order, then acknowledge them via
+    /// `set_log_index` so the device can reclaim the buffer space. Keeps
+    /// pulling until the buffer comes back short, so a long-running service
+    /// can call this periodically without tracking buffer state itself.
+    ///
+    /// `prev_digest` seeds the chain as in `LogEntries::verify_chain`: pass
+    /// `None` on the first call, then the returned digest on each subsequent
+    /// call to resume from a trusted checkpoint.
+    ///
+    /// Doesn't go through `with_retry`: that retries its whole closure from
+    /// scratch, but draining runs several rounds against the device, and
+    /// `handler` may already have been called (and entries acknowledged)
+    /// for earlier rounds by the time a later one hits an expired session.
+    /// Retrying from the original `prev_digest` would replay those rounds
+    /// through `handler` a second time, so the retry here resumes from the
+    /// digest of the last entry `handler` actually saw instead. A session
+    /// that expires after `handler` ran for a batch but before that
+    /// batch's `set_log_index` lands makes the retry fail: the device
+    /// serves that batch again, and it doesn't chain from the digest the
+    /// retry resumes from, so verification returns a `ProtocolError`.
+    pub fn drain_log_entries(
+        &mut self,
+        prev_digest: Option<LogDigest>,
+        mut handler: impl FnMut(&LogEntry),
+    ) -> Result<Option<LogDigest>, SessionError> {
+        let resume_digest = Cell::new(prev_digest);
+        let mut handler = |entry: &LogEntry| {
+            resume_digest.set(Some(entry.digest));
+            handler(entry);
+        };
+
+        let result = audit::drain_log_entries(self.session()?, prev_digest, &mut handler);
+
+        match result {
+            Err(ref e) if should_retry(self.reconnect, e) => {
+                self.session = Some(self.create_session()?);
+                audit::drain_log_entries(self.session()?, resume_digest.get(), &mut handler)
+            }
+            other => other,
+        }
+    }
+
+    /// Borrow the current session, lazily establishing one if none exists yet
+    fn session(&mut self) -> Result<&mut Session<Box<dyn Connection>>, SessionError> {
+        if self.session.is_none() {
+            self.session = Some(self.create_session()?);
+        }
+
+        Ok(self.session.as_mut().expect("session just populated"))
+    }
+
+    /// Open a fresh connection and establish a new session on top of it
+    fn create_session(&self) -> Result<Session<Box<dyn Connection>>, SessionError> {
+        let connection = self
+            .connector
+            .connect()
+            .map_err(|e| err!(SessionErrorKind::ConnectionFailed, "{}", e))?;
+
+        session::create_from_credentials(connection, &self.credentials)
+    }
+
+    /// Run `f` against the current session, retrying once against a freshly
+    /// established session if it fails because the device expired the
+    /// previous one (only when `reconnect` is enabled)
+    fn with_retry<T>(
+        &mut self,
+        mut f: impl FnMut(&mut Session<Box<dyn Connection>>) -> Result<T, SessionError>,
+    ) -> Result<T, SessionError> {
+        let result = f(self.session()?);
+
+        match result {
+            Err(ref e) if should_retry(self.reconnect, e) => {
+                self.session = Some(self.create_session()?);
+                f(self.session()?)
+            }
+            other => other,
+        }
+    }
+}
+
+/// Whether a failed command should be retried against a freshly established
+/// session: only when reconnecting is enabled, and only when the device
+/// reported the previous session as expired rather than some other failure
+/// a fresh session wouldn't fix.
+fn should_retry(reconnect: bool, error: &SessionError) -> bool {
+    reconnect && error.kind() == SessionErrorKind::SessionExpired
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_retry_only_when_reconnect_enabled_and_session_expired() {
+        let expired = SessionError::new(SessionErrorKind::SessionExpired, "session expired");
+        let other = SessionError::new(SessionErrorKind::ProtocolError, "bad response");
+
+        assert!(should_retry(true, &expired));
+        assert!(!should_retry(false, &expired));
+        assert!(!should_retry(true, &other));
+    }
+}
@@ -0,0 +1,156 @@
+//! Simulated audit log kept by `MockHsm`, so `get_log_entries` and
+//! `set_log_index` can be exercised against the mock the same way they
+//! would be against a real device.
+
+use crate::{
+    audit::commands::{LogDigest, LogEntries, LogEntry, LOG_DIGEST_SIZE, LOG_ENTRIES_CAPACITY},
+    command, object, response,
+    serialization::serialize,
+};
+use sha2::{Digest, Sha256};
+
+/// Seed used as the "previous digest" for the first entry ever logged
+const INITIAL_LOG_DIGEST: LogDigest = LogDigest([0xff; LOG_DIGEST_SIZE]);
+
+/// In-memory simulation of the `YubiHSM 2`'s tamper-evident audit log
+pub(super) struct AuditLog {
+    entries: Vec<LogEntry>,
+    tick: u32,
+    last_consumed_item: u16,
+    last_item: u16,
+    last_digest: LogDigest,
+    unlogged_boot_events: u16,
+    unlogged_auth_events: u16,
+}
+
+impl Default for AuditLog {
+    fn default() -> Self {
+        AuditLog {
+            entries: Vec::new(),
+            tick: 0,
+            last_consumed_item: 0,
+            last_item: 0,
+            last_digest: INITIAL_LOG_DIGEST,
+            unlogged_boot_events: 0,
+            unlogged_auth_events: 0,
+        }
+    }
+}
+
+impl AuditLog {
+    /// Append a log entry for a command the mock just processed, chaining
+    /// its digest off of the previous entry's the same way a real device
+    /// would.
+    ///
+    /// The chain continues from `last_item`/`last_digest` rather than
+    /// `self.entries.last()`, since `set_index` drops acknowledged entries
+    /// out of `self.entries` and numbering/chaining must survive that.
+    pub(super) fn append(
+        &mut self,
+        cmd: command::Code,
+        length: u16,
+        session_key: object::Id,
+        target_key: object::Id,
+        result: response::Code,
+    ) {
+        self.tick += 1;
+
+        let prev_digest = self.last_digest;
+        let item = self.last_item.wrapping_add(1);
+
+        let mut entry = LogEntry {
+            item,
+            cmd,
+            length,
+            session_key,
+            target_key,
+            second_key: 0,
+            result,
+            tick: self.tick,
+            digest: LogDigest([0u8; LOG_DIGEST_SIZE]),
+        };
+
+        let entry_bytes = serialize(&entry).expect("log entry always serializes");
+
+        let mut hasher = Sha256::new();
+        hasher.input(&entry_bytes[..LOG_DIGEST_SIZE]);
+        hasher.input(prev_digest.as_ref());
+
+        let mut digest = [0u8; LOG_DIGEST_SIZE];
+        digest.copy_from_slice(&hasher.result()[..LOG_DIGEST_SIZE]);
+        entry.digest = LogDigest(digest);
+
+        self.last_item = item;
+        self.last_digest = entry.digest;
+
+        // `self.entries` only ever holds entries the caller hasn't yet
+        // acknowledged via `set_index` (see below), so its length already
+        // reflects the unconsumed backlog rather than lifetime entries.
+        // A session-scoped command (identified by a non-zero `session_key`)
+        // that gets evicted here is an authenticated event going unlogged;
+        // anything else (e.g. the boot/reset marker) is a boot event.
+        if self.entries.len() >= LOG_ENTRIES_CAPACITY {
+            let evicted = self.entries.remove(0);
+
+            if evicted.session_key != 0 {
+                self.unlogged_auth_events = self.unlogged_auth_events.saturating_add(1);
+            } else {
+                self.unlogged_boot_events = self.unlogged_boot_events.saturating_add(1);
+            }
+        }
+
+        self.entries.push(entry);
+    }
+
+    /// Build a `get_log_entries` response from the entries not yet
+    /// acknowledged via `set_log_index`
+    pub(super) fn get_entries(&self) -> LogEntries {
+        let entries = self.entries.clone();
+
+        LogEntries {
+            unlogged_boot_events: self.unlogged_boot_events,
+            unlogged_auth_events: self.unlogged_auth_events,
+            num_entries: entries.len() as u8,
+            entries,
+        }
+    }
+
+    /// Acknowledge every entry up to and including `item`, discarding them
+    /// so the simulated buffer's unconsumed backlog (and thus the eviction
+    /// threshold in `append`) reflects only what the caller hasn't seen yet
+    pub(super) fn set_index(&mut self, item: u16) {
+        self.last_consumed_item = item;
+        self.entries.retain(|entry| entry.item > item);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn append_counts_evicted_session_scoped_entries_as_unlogged_auth_events() {
+        let mut log = AuditLog::default();
+
+        for _ in 0..=LOG_ENTRIES_CAPACITY {
+            log.append(command::Code::GetLogEntries, 0, 1, 0, response::Code::Success);
+        }
+
+        assert_eq!(log.unlogged_auth_events, 1);
+        assert_eq!(log.unlogged_boot_events, 0);
+        assert_eq!(log.entries.len(), LOG_ENTRIES_CAPACITY);
+    }
+
+    #[test]
+    fn append_counts_evicted_session_less_entries_as_unlogged_boot_events() {
+        let mut log = AuditLog::default();
+
+        for _ in 0..=LOG_ENTRIES_CAPACITY {
+            log.append(command::Code::GetLogEntries, 0, 0, 0, response::Code::Success);
+        }
+
+        assert_eq!(log.unlogged_boot_events, 1);
+        assert_eq!(log.unlogged_auth_events, 0);
+        assert_eq!(log.entries.len(), LOG_ENTRIES_CAPACITY);
+    }
+}
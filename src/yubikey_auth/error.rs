@@ -0,0 +1,46 @@
+//! Error types for the `yubikey_auth` module
+
+use std::fmt::{self, Display};
+
+/// Errors interacting with a YubiKey's YubiHSM-Auth applet
+#[derive(Debug)]
+pub struct YubiKeyAuthError {
+    kind: YubiKeyAuthErrorKind,
+    description: String,
+}
+
+impl YubiKeyAuthError {
+    /// Create a new `YubiKeyAuthError` of the given kind with a human-readable description
+    pub fn new(kind: YubiKeyAuthErrorKind, description: impl Into<String>) -> Self {
+        YubiKeyAuthError {
+            kind,
+            description: description.into(),
+        }
+    }
+
+    /// Kind of error that occurred
+    pub fn kind(&self) -> YubiKeyAuthErrorKind {
+        self.kind
+    }
+}
+
+impl Display for YubiKeyAuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.description)
+    }
+}
+
+impl std::error::Error for YubiKeyAuthError {}
+
+/// Kinds of errors which occur when talking to the YubiHSM-Auth applet
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum YubiKeyAuthErrorKind {
+    /// The requested credential label doesn't exist on the applet
+    CredentialNotFound,
+
+    /// The PIN was rejected by the applet
+    AuthenticationFailed,
+
+    /// The applet (or its smartcard transport) returned an error
+    AppletError,
+}
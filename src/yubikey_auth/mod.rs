@@ -0,0 +1,226 @@
+//! Deriving `YubiHSM 2` SCP03 session keys from a YubiKey's YubiHSM-Auth
+//! applet, so the long-term authentication key material never has to live
+//! in host memory (unlike `create_session_from_password`).
+//!
+//! <https://developers.yubico.com/YubiHSM-Auth/>
+
+mod error;
+
+pub use self::error::{YubiKeyAuthError, YubiKeyAuthErrorKind};
+
+use crate::{
+    adapter::Connection,
+    session::{self, Session, SessionError, SessionErrorKind, SessionKeys},
+};
+
+/// Label identifying a credential stored on a YubiHSM-Auth applet
+pub type CredentialLabel = String;
+
+/// A connection to the YubiHSM-Auth applet on a YubiKey.
+///
+/// Implementations drive whatever smartcard transport (PC/SC, CCID, etc.)
+/// is used to talk to the token; this crate only needs the two applet
+/// operations below.
+pub trait YubiKeyAuthApplet {
+    /// List the labels of the credentials stored on this applet
+    fn list_credentials(&mut self) -> Result<Vec<CredentialLabel>, YubiKeyAuthError>;
+
+    /// Perform the applet's `Calculate` operation: send the host's 8-byte
+    /// challenge, receive the applet's challenge, and return the three
+    /// SCP03 session keys (S-ENC, S-MAC, S-RMAC) the applet derived from
+    /// them and the long-term key identified by `label`
+    fn calculate(
+        &mut self,
+        label: &CredentialLabel,
+        pin: &str,
+        host_challenge: [u8; 8],
+    ) -> Result<SessionKeys, YubiKeyAuthError>;
+}
+
+/// Credentials which derive SCP03 session keys via a YubiHSM-Auth applet's
+/// `Calculate` operation, rather than running a password through the
+/// default key-derivation like `create_session_from_password` does.
+pub struct YubiKeyCredentials<'a, T: YubiKeyAuthApplet> {
+    applet: &'a mut T,
+    label: CredentialLabel,
+    pin: String,
+}
+
+impl<'a, T: YubiKeyAuthApplet> YubiKeyCredentials<'a, T> {
+    /// Reference a credential by label, to be unlocked with the given PIN
+    /// when session keys are derived
+    pub fn new(applet: &'a mut T, label: impl Into<CredentialLabel>, pin: impl Into<String>) -> Self {
+        YubiKeyCredentials {
+            applet,
+            label: label.into(),
+            pin: pin.into(),
+        }
+    }
+
+    /// Derive the SCP03 session keys for this credential, given the host's
+    /// challenge for the ongoing session establishment
+    pub fn derive_session_keys(
+        &mut self,
+        host_challenge: [u8; 8],
+    ) -> Result<SessionKeys, YubiKeyAuthError> {
+        self.applet.calculate(&self.label, &self.pin, host_challenge)
+    }
+}
+
+/// Enumerate the credential labels available on a YubiHSM-Auth applet
+pub fn list_credentials<T: YubiKeyAuthApplet>(
+    applet: &mut T,
+) -> Result<Vec<CredentialLabel>, YubiKeyAuthError> {
+    applet.list_credentials()
+}
+
+/// Open a `Session` authenticated via a YubiHSM-Auth applet's `Calculate`
+/// operation, the YubiKey-backed analogue of `create_session_from_password`:
+/// the long-term key never leaves the applet, only the SCP03 session keys
+/// it derives do.
+///
+/// `host_challenge` is handed to the applet so it can derive S-ENC/S-MAC/
+/// S-RMAC the same way the real device would from its own copy of the
+/// long-term key.
+///
+// TODO: SCP03 key derivation is a function of this same host challenge
+// (and the device's card challenge), but `create_from_credentials` has no
+// way to accept a caller-supplied host challenge and establishes the
+// session with one it generates internally. Until it grows that, the
+// challenge given to the applet here and the one actually used in the
+// handshake can diverge, and the resulting session keys won't match what
+// the device computes.
+pub fn create_session<C: Connection, T: YubiKeyAuthApplet>(
+    connection: C,
+    credentials: &mut YubiKeyCredentials<'_, T>,
+    host_challenge: [u8; 8],
+) -> Result<Session<C>, SessionError> {
+    let session_keys = credentials
+        .derive_session_keys(host_challenge)
+        .map_err(|e| err!(SessionErrorKind::AuthenticationFailed, "{}", e))?;
+
+    session::create_from_credentials(connection, &session::Credentials::from_session_keys(session_keys))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapter::AdapterError;
+
+    /// A `YubiKeyAuthApplet` that records the arguments it was called with,
+    /// in lieu of a real smartcard transport, and either succeeds with
+    /// fixed session keys or reports the credential as missing
+    struct FakeApplet {
+        credentials: Vec<CredentialLabel>,
+        calculate_calls: Vec<(CredentialLabel, String, [u8; 8])>,
+        calculate_succeeds: bool,
+    }
+
+    /// Session keys returned by `FakeApplet` on its success path. The
+    /// values themselves are arbitrary: nothing in this module inspects
+    /// them, only whether the applet's success or failure comes back
+    /// through `YubiKeyCredentials`/`create_session`.
+    fn fake_session_keys() -> SessionKeys {
+        SessionKeys::new([0x11; 16], [0x22; 16], [0x33; 16])
+    }
+
+    impl YubiKeyAuthApplet for FakeApplet {
+        fn list_credentials(&mut self) -> Result<Vec<CredentialLabel>, YubiKeyAuthError> {
+            Ok(self.credentials.clone())
+        }
+
+        fn calculate(
+            &mut self,
+            label: &CredentialLabel,
+            pin: &str,
+            host_challenge: [u8; 8],
+        ) -> Result<SessionKeys, YubiKeyAuthError> {
+            self.calculate_calls
+                .push((label.clone(), pin.to_owned(), host_challenge));
+
+            if self.calculate_succeeds {
+                Ok(fake_session_keys())
+            } else {
+                Err(YubiKeyAuthError::new(
+                    YubiKeyAuthErrorKind::CredentialNotFound,
+                    format!("no such credential: {}", label),
+                ))
+            }
+        }
+    }
+
+    /// A `Connection` that panics if anything actually tries to send a
+    /// message over it, for exercising the parts of `create_session` that
+    /// should fail before ever reaching the device
+    struct NullConnection;
+
+    impl Connection for NullConnection {
+        fn send_message(&mut self, _message: Vec<u8>) -> Result<Vec<u8>, AdapterError> {
+            unreachable!("should fail deriving session keys before sending anything")
+        }
+    }
+
+    #[test]
+    fn list_credentials_delegates_to_applet() {
+        let mut applet = FakeApplet {
+            credentials: vec!["default".to_owned()],
+            calculate_calls: vec![],
+            calculate_succeeds: false,
+        };
+
+        let labels = list_credentials(&mut applet).expect("list should succeed");
+        assert_eq!(labels, vec!["default".to_owned()]);
+    }
+
+    #[test]
+    fn derive_session_keys_threads_label_pin_and_challenge_to_the_applet() {
+        let mut applet = FakeApplet {
+            credentials: vec![],
+            calculate_calls: vec![],
+            calculate_succeeds: false,
+        };
+        let mut credentials = YubiKeyCredentials::new(&mut applet, "default", "123456");
+
+        credentials
+            .derive_session_keys([1, 2, 3, 4, 5, 6, 7, 8])
+            .expect_err("fake applet always reports the credential missing");
+
+        assert_eq!(
+            applet.calculate_calls,
+            vec![(
+                "default".to_owned(),
+                "123456".to_owned(),
+                [1, 2, 3, 4, 5, 6, 7, 8]
+            )]
+        );
+    }
+
+    #[test]
+    fn derive_session_keys_succeeds_when_the_applet_does() {
+        let mut applet = FakeApplet {
+            credentials: vec![],
+            calculate_calls: vec![],
+            calculate_succeeds: true,
+        };
+        let mut credentials = YubiKeyCredentials::new(&mut applet, "default", "123456");
+
+        credentials
+            .derive_session_keys([1, 2, 3, 4, 5, 6, 7, 8])
+            .expect("fake applet configured to succeed");
+    }
+
+    #[test]
+    fn create_session_surfaces_applet_failure_as_authentication_failed() {
+        let mut applet = FakeApplet {
+            credentials: vec![],
+            calculate_calls: vec![],
+            calculate_succeeds: false,
+        };
+        let mut credentials = YubiKeyCredentials::new(&mut applet, "default", "123456");
+
+        let error = create_session(NullConnection, &mut credentials, [1, 2, 3, 4, 5, 6, 7, 8])
+            .expect_err("fake applet always reports the credential missing");
+
+        assert_eq!(error.kind(), SessionErrorKind::AuthenticationFailed);
+    }
+}
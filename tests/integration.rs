@@ -1,20 +1,13 @@
 extern crate yubihsm_client;
 
+use yubihsm_client::{adapter::HttpConnector, session, KeyId, Session, SessionId};
 #[cfg(feature = "mockhsm")]
-use std::thread;
-
-use yubihsm_client::{Connector, KeyId, Session, SessionId};
-#[cfg(feature = "mockhsm")]
-use yubihsm_client::mockhsm::MockHSM;
+use yubihsm_client::mockhsm::MockHsm;
 
 /// Test against the real yubihsm-connector
 #[cfg(not(feature = "mockhsm"))]
 const YUBIHSM_ADDR: &str = "127.0.0.1:12345";
 
-// TODO: pick an open port automatically
-#[cfg(feature = "mockhsm")]
-const MOCKHSM_ADDR: &str = "127.0.0.1:54321";
-
 /// Default auth key ID slot
 const DEFAULT_AUTH_KEY_ID: KeyId = 1;
 
@@ -24,37 +17,27 @@ const DEFAULT_PASSWORD: &str = "password";
 #[cfg(not(feature = "mockhsm"))]
 #[test]
 fn yubihsm_integration_test() {
-    let conn = Connector::open(&format!("http://{}", YUBIHSM_ADDR))
-        .unwrap_or_else(|err| panic!("cannot open connection to yubihsm-connector: {:?}", err));
+    let connector = HttpConnector::new(&format!("http://{}", YUBIHSM_ADDR));
 
-    let mut session = conn.create_session_from_password(DEFAULT_AUTH_KEY_ID, DEFAULT_PASSWORD)
+    let mut session = session::create_from_password(&connector, DEFAULT_AUTH_KEY_ID, DEFAULT_PASSWORD)
         .unwrap_or_else(|err| panic!("error creating session: {:?}", err));
 
     assert_eq!(session.id(), SessionId::new(0).unwrap());
     echo_test(&mut session);
 }
 
-#[cfg(feature = "mockhsm")]
-fn start_mockhsm(num_requests: usize) -> thread::JoinHandle<()> {
-    thread::spawn(move || MockHSM::new(MOCKHSM_ADDR).unwrap().run(num_requests))
-}
-
 #[cfg(feature = "mockhsm")]
 #[test]
 fn mockhsm_integration_test() {
-    let num_requests = 4;
-    let mockhsm_thread = start_mockhsm(num_requests);
-
-    let conn = Connector::open(&format!("http://{}", MOCKHSM_ADDR))
-        .unwrap_or_else(|err| panic!("cannot open connection to mockhsm: {:?}", err));
+    // No TCP listener or background thread needed: `MockHsm` implements
+    // `Connector` directly and runs entirely in-process.
+    let mockhsm = MockHsm::new();
 
-    let mut session = conn.create_session_from_password(DEFAULT_AUTH_KEY_ID, DEFAULT_PASSWORD)
+    let mut session = session::create_from_password(&mockhsm, DEFAULT_AUTH_KEY_ID, DEFAULT_PASSWORD)
         .unwrap_or_else(|err| panic!("error creating session: {:?}", err));
 
     assert_eq!(session.id(), SessionId::new(0).unwrap());
     echo_test(&mut session);
-
-    mockhsm_thread.join().unwrap();
 }
 
 // Send a simple echo request
@@ -65,4 +48,34 @@ fn echo_test(session: &mut Session) {
         .unwrap_or_else(|err| panic!("error sending echo: {:?}", err));
 
     assert_eq!(&message[..], &echo_result[..]);
+}
+
+// Draining the audit log must terminate even though the `get_log_entries`/
+// `set_log_index` round-trip used to drain it logs entries of its own, so
+// the buffer this pulls from is never actually empty.
+#[cfg(feature = "mockhsm")]
+#[test]
+fn mockhsm_drain_log_entries_test() {
+    use yubihsm_client::audit::drain_log_entries;
+
+    let mockhsm = MockHsm::new();
+
+    let mut session = session::create_from_password(&mockhsm, DEFAULT_AUTH_KEY_ID, DEFAULT_PASSWORD)
+        .unwrap_or_else(|err| panic!("error creating session: {:?}", err));
+
+    echo_test(&mut session);
+    echo_test(&mut session);
+
+    let mut drained = Vec::new();
+    let prev_digest = drain_log_entries(&mut session, None, |entry| drained.push(entry.item))
+        .unwrap_or_else(|err| panic!("error draining log entries: {:?}", err));
+
+    assert!(!drained.is_empty());
+    assert!(prev_digest.is_some());
+
+    // A second drain picks up right where the first left off, including
+    // the entries the first drain's own `get_log_entries`/`set_log_index`
+    // calls logged, and still terminates.
+    drain_log_entries(&mut session, prev_digest, |_| {})
+        .unwrap_or_else(|err| panic!("error draining log entries again: {:?}", err));
 }
\ No newline at end of file